@@ -0,0 +1,167 @@
+// transgui-ng - next gen remote GUI for transmission torrent daemon
+// Copyright (C) 2022  qu1ck (mail at qu1ck.org)
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Launch-at-login registration. Each platform has its own idea of what
+//! "autostart" means, so we always ask the platform for the real answer
+//! instead of trusting a stored flag: the user may have removed the
+//! registration by hand (unchecking it in their OS settings, deleting the
+//! plist, etc) and we want the tray checkbox to reflect that.
+
+const APP_NAME: &str = "TrguiNG";
+const MINIMIZED_FLAG: &str = "--minimized";
+
+pub fn is_enabled() -> bool {
+    platform::is_enabled()
+}
+
+pub fn set_enabled(enabled: bool) -> Result<(), String> {
+    if enabled {
+        platform::enable()
+    } else {
+        platform::disable()
+    }
+}
+
+fn exe_path() -> Result<std::path::PathBuf, String> {
+    std::env::current_exe().map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{exe_path, APP_NAME, MINIMIZED_FLAG};
+    use winreg::enums::HKEY_CURRENT_USER;
+    use winreg::RegKey;
+
+    const RUN_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+
+    pub fn is_enabled() -> bool {
+        let Ok(key) = RegKey::predef(HKEY_CURRENT_USER).open_subkey(RUN_KEY) else {
+            return false;
+        };
+        key.get_value::<String, _>(APP_NAME).is_ok()
+    }
+
+    pub fn enable() -> Result<(), String> {
+        let exe = exe_path()?;
+        let (key, _) = RegKey::predef(HKEY_CURRENT_USER)
+            .create_subkey(RUN_KEY)
+            .map_err(|e| e.to_string())?;
+        key.set_value(
+            APP_NAME,
+            &format!("\"{}\" {}", exe.display(), MINIMIZED_FLAG),
+        )
+        .map_err(|e| e.to_string())
+    }
+
+    pub fn disable() -> Result<(), String> {
+        let key = RegKey::predef(HKEY_CURRENT_USER)
+            .open_subkey_with_flags(RUN_KEY, winreg::enums::KEY_WRITE)
+            .map_err(|e| e.to_string())?;
+        match key.delete_value(APP_NAME) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{exe_path, MINIMIZED_FLAG};
+
+    fn plist_path() -> Option<std::path::PathBuf> {
+        Some(dirs::home_dir()?.join("Library/LaunchAgents/org.trguing.app.plist"))
+    }
+
+    pub fn is_enabled() -> bool {
+        plist_path().map(|p| p.exists()).unwrap_or(false)
+    }
+
+    pub fn enable() -> Result<(), String> {
+        let exe = exe_path()?;
+        let path = plist_path().ok_or("no home directory")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let plist = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>org.trguing.app</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+        <string>{}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+            exe.display(),
+            MINIMIZED_FLAG
+        );
+        std::fs::write(path, plist).map_err(|e| e.to_string())
+    }
+
+    pub fn disable() -> Result<(), String> {
+        let path = plist_path().ok_or("no home directory")?;
+        match std::fs::remove_file(path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{exe_path, APP_NAME, MINIMIZED_FLAG};
+
+    fn desktop_entry_path() -> Option<std::path::PathBuf> {
+        Some(dirs::config_dir()?.join("autostart/trguing.desktop"))
+    }
+
+    pub fn is_enabled() -> bool {
+        desktop_entry_path().map(|p| p.exists()).unwrap_or(false)
+    }
+
+    pub fn enable() -> Result<(), String> {
+        let exe = exe_path()?;
+        let path = desktop_entry_path().ok_or("no config directory")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let entry = format!(
+            "[Desktop Entry]\nType=Application\nName={}\nExec=\"{}\" {}\nX-GNOME-Autostart-enabled=true\n",
+            APP_NAME,
+            exe.display(),
+            MINIMIZED_FLAG
+        );
+        std::fs::write(path, entry).map_err(|e| e.to_string())
+    }
+
+    pub fn disable() -> Result<(), String> {
+        let path = desktop_entry_path().ok_or("no config directory")?;
+        match std::fs::remove_file(path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+// transgui-ng - next gen remote GUI for transmission torrent daemon
+// Copyright (C) 2022  qu1ck (mail at qu1ck.org)
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::fs;
+
+use tauri::{async_runtime, AppHandle, Manager, State, WindowBuilder, WindowUrl};
+
+use crate::poller::{PollerConfig, PollerHandle};
+use crate::speed_schedule::SpeedSchedule;
+use crate::torrentcache::TorrentCacheHandle;
+use crate::windows::{ServerWindow, WindowManager};
+
+#[tauri::command]
+pub fn read_file(path: String) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn shell_open(path: String) -> Result<(), String> {
+    open::that(path).map_err(|e| e.to_string())
+}
+
+/// Configure a poller's target server. With no `label`, this reconfigures
+/// the main window's (global) poller; with one, it reconfigures the named
+/// server window's poller instead.
+#[tauri::command]
+pub async fn set_poller_config(
+    poller: State<'_, PollerHandle>,
+    manager: State<'_, WindowManager>,
+    label: Option<String>,
+    config: PollerConfig,
+) -> Result<(), String> {
+    let target = match &label {
+        Some(label) => manager
+            .poller(label)
+            .ok_or_else(|| format!("no window with label {}", label))?,
+        None => (*poller).clone(),
+    };
+    target.0.lock().await.set_config(config);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_autostart(enabled: bool) -> Result<(), String> {
+    crate::autostart::set_enabled(enabled)
+}
+
+/// Open a new window polling `server_id`, independently of whichever server
+/// the main window is connected to. A window pointed at a `server_id` that
+/// some other open window already watches shares that window's poller and
+/// cache instead of standing up a duplicate connection.
+#[tauri::command]
+pub async fn open_server_window(
+    app: AppHandle,
+    manager: State<'_, WindowManager>,
+    server_id: String,
+    config: PollerConfig,
+) -> Result<String, String> {
+    let label = manager.next_label();
+    // Stay hidden until the title is set, same as the main window's reopen
+    // path, so the window never flashes with its default title first.
+    let window = WindowBuilder::new(&app, &label, WindowUrl::App("index.html".into()))
+        .visible(false)
+        .build()
+        .map_err(|e| e.to_string())?;
+    window.set_title(&format!("TrguiNG — {}", server_id)).ok();
+    window.show().ok();
+    window.set_focus().ok();
+
+    {
+        let app = app.clone();
+        let label = label.clone();
+        window.on_window_event(move |event| {
+            if let tauri::WindowEvent::Destroyed = event {
+                let app = app.clone();
+                let label = label.clone();
+                async_runtime::spawn(async move {
+                    let manager: State<WindowManager> = app.state();
+                    if let Some(window) = manager.remove(&label) {
+                        window.poller.0.lock().await.remove_subscriber(&label);
+                        if !manager.has_window_for(&window.server_id) {
+                            window.poller.stop();
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    let (poller, cache) = match manager.shared(&server_id) {
+        Some((poller, cache)) => {
+            poller.0.lock().await.add_subscriber(&label);
+            (poller, cache)
+        }
+        None => {
+            let poller = PollerHandle::new();
+            let cache = TorrentCacheHandle::default();
+            let app: std::sync::Arc<AppHandle> = std::sync::Arc::new(app);
+            let mut locked = poller.0.lock().await;
+            locked.set_app_handle(&app, &label);
+            locked.set_cache(cache.clone());
+            locked.set_config(config);
+            drop(locked);
+            (poller, cache)
+        }
+    };
+
+    manager.insert(
+        label.clone(),
+        ServerWindow {
+            server_id,
+            poller,
+            cache,
+        },
+    );
+    Ok(label)
+}
+
+#[tauri::command]
+pub async fn set_speed_schedule(
+    poller: State<'_, PollerHandle>,
+    schedule: SpeedSchedule,
+) -> Result<(), String> {
+    let mut poller = poller.0.lock().await;
+    poller.set_schedule(schedule);
+    Ok(())
+}
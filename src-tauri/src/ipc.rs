@@ -0,0 +1,106 @@
+// transgui-ng - next gen remote GUI for transmission torrent daemon
+// Copyright (C) 2022  qu1ck (mail at qu1ck.org)
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use tauri::AppHandle;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+
+const PORT: u16 = 44932;
+
+/// Single-instance IPC: the first process binds a local TCP port and listens
+/// for argv forwarded from subsequently launched instances.
+pub struct Ipc {
+    listener: Option<Arc<Mutex<TcpListener>>>,
+    pub listening: bool,
+}
+
+impl Ipc {
+    pub fn new() -> Self {
+        Ipc {
+            listener: None,
+            listening: false,
+        }
+    }
+
+    /// Try to become the primary instance by binding the well-known port.
+    pub fn try_bind(&mut self) {
+        if let Ok(listener) = std::net::TcpListener::bind(("127.0.0.1", PORT)) {
+            listener.set_nonblocking(true).ok();
+            if let Ok(listener) = TcpListener::from_std(listener) {
+                self.listener = Some(Arc::new(Mutex::new(listener)));
+                self.listening = true;
+            }
+        }
+    }
+
+    pub async fn listen(&mut self, app: Arc<AppHandle>) -> Result<(), std::io::Error> {
+        let listener = match &self.listener {
+            Some(listener) => listener.clone(),
+            None => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    "not bound",
+                ))
+            }
+        };
+
+        tauri::async_runtime::spawn(async move {
+            loop {
+                let accepted = {
+                    let listener = listener.lock().await;
+                    listener.accept().await
+                };
+                if let Ok((stream, _)) = accepted {
+                    Self::handle_connection(stream, &app).await;
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    async fn handle_connection(mut stream: TcpStream, app: &Arc<AppHandle>) {
+        use tokio::io::AsyncReadExt;
+
+        let mut buf = Vec::new();
+        if stream.read_to_end(&mut buf).await.is_ok() {
+            if let Ok(args) = serde_json::from_slice::<Vec<String>>(&buf) {
+                app.emit_all("listener-start", args).ok();
+            }
+        }
+    }
+
+    pub fn start(&self) {}
+
+    pub fn stop(&mut self) {
+        self.listening = false;
+    }
+
+    /// Forward this instance's args to the already-running primary instance.
+    pub async fn send(&self, args: &[String]) -> Result<(), std::io::Error> {
+        if self.listening {
+            return Ok(());
+        }
+        use tokio::io::AsyncWriteExt;
+
+        let mut stream = TcpStream::connect(("127.0.0.1", PORT)).await?;
+        let payload = serde_json::to_vec(args)?;
+        stream.write_all(&payload).await?;
+        Ok(())
+    }
+}
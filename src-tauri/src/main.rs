@@ -29,18 +29,32 @@ use tauri::{
 };
 use torrentcache::TorrentCacheHandle;
 
+mod autostart;
 mod commands;
 mod ipc;
 mod poller;
+mod speed_schedule;
 mod torrentcache;
 mod tray;
+mod window_state;
+mod windows;
 
 struct ListenerHandle(Arc<Mutex<ipc::Ipc>>);
 
 fn setup(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
     let config = app.config();
     let cli_config = &config.tauri.cli.as_ref().unwrap();
-    let args = get_matches(cli_config, app.package_info()).unwrap().args;
+    // Autostart launches pass `--minimized` (see `autostart.rs`); if that flag
+    // isn't declared in the CLI config, clap rejects the whole command line
+    // rather than just the unknown flag, so this must not panic on a launch
+    // we don't control the arguments of.
+    let args = match get_matches(cli_config, app.package_info()) {
+        Ok(matches) => matches.args,
+        Err(e) => {
+            println!("Unable to parse CLI arguments: {}", e);
+            Default::default()
+        }
+    };
 
     if args.contains_key("help") {
         println!("{}", args["help"].value.as_str().unwrap());
@@ -49,14 +63,13 @@ fn setup(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
     }
 
     let mut torrents: Vec<String> = vec![];
-    if args["torrent"].value.is_array() {
-        torrents = args["torrent"]
-            .value
-            .as_array()
-            .unwrap()
-            .into_iter()
-            .map(|v| v.as_str().unwrap().to_string())
-            .collect();
+    if let Some(torrent_arg) = args.get("torrent") {
+        if let Some(values) = torrent_arg.value.as_array() {
+            torrents = values
+                .iter()
+                .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                .collect();
+        }
     }
 
     let app: Arc<AppHandle> = app.handle().into();
@@ -67,7 +80,7 @@ fn setup(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
     async_runtime::spawn(async move {
         let poller_state: State<PollerHandle> = app.state();
         let mut poller = poller_state.0.lock().await;
-        poller.set_app_handle(&app);
+        poller.set_app_handle(&app, "main");
 
         let mut listener = listener_mutex.lock().await;
         if let Err(_) = listener.listen(app.clone()).await {
@@ -88,7 +101,18 @@ fn setup(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
                     listener.start();
                 });
             });
-            main_window.show().ok();
+            if let Some(state) = window_state::load(&app) {
+                window_state::apply(&main_window, &state);
+            }
+            let minimized = args
+                .get("minimized")
+                .map(|a| a.value.as_bool().unwrap_or(false))
+                .unwrap_or(false);
+            if !minimized {
+                main_window.show().ok();
+            } else {
+                tray::set_showhide_title("Show");
+            }
         } else {
             main_window.close().ok();
         }
@@ -107,11 +131,15 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             commands::read_file,
             commands::shell_open,
-            commands::set_poller_config
+            commands::set_poller_config,
+            commands::set_speed_schedule,
+            commands::set_autostart,
+            commands::open_server_window
         ])
         .manage(ListenerHandle(Arc::new(Mutex::new(ipc))))
         .manage(TorrentCacheHandle::default())
         .manage(PollerHandle::default())
+        .manage(windows::WindowManager::default())
         .system_tray(tray::create_tray())
         .on_system_tray_event(tray::on_tray_event)
         .setup(setup)
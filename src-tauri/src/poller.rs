@@ -0,0 +1,487 @@
+// transgui-ng - next gen remote GUI for transmission torrent daemon
+// Copyright (C) 2022  qu1ck (mail at qu1ck.org)
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+
+use chrono::{Datelike, Timelike};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use serde_json::value::RawValue;
+use tauri::api::notification::Notification;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::{mpsc, Mutex};
+
+use crate::speed_schedule::{self, SpeedSchedule};
+use crate::torrentcache::{CachedTorrent, TorrentCacheHandle};
+use crate::tray;
+
+const DEFAULT_INTERVAL_SECS: u64 = 5;
+const RECENT_TORRENTS_LIMIT: usize = 5;
+
+/// Connection details for the server the poller keeps an eye on. Set from
+/// the frontend whenever the user picks or edits a server.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PollerConfig {
+    pub url: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    #[serde(default = "default_interval")]
+    pub interval_secs: u64,
+    #[serde(default)]
+    pub notifications_enabled: bool,
+}
+
+fn default_interval() -> u64 {
+    DEFAULT_INTERVAL_SECS
+}
+
+/// One recently-active torrent, enough to populate the tray submenu.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RecentTorrent {
+    pub id: i64,
+    pub name: String,
+    pub status: i64,
+}
+
+/// Aggregate snapshot pushed to the tray after every poll.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AggregateStats {
+    pub download_rate: i64,
+    pub upload_rate: i64,
+    pub active: usize,
+    pub seeding: usize,
+    pub paused: usize,
+    pub recent: Vec<RecentTorrent>,
+    pub turtle_mode: bool,
+}
+
+pub struct Poller {
+    app: Option<Arc<AppHandle>>,
+    config: Option<PollerConfig>,
+    client: reqwest::Client,
+    session_id: String,
+    stats_tx: Option<mpsc::Sender<AggregateStats>>,
+    schedule: Option<SpeedSchedule>,
+    /// The last `alt-speed-enabled` value *we* decided on via the schedule,
+    /// used to only flip the setting on an actual schedule transition. This
+    /// must never be synced from the daemon's observed state: if the user
+    /// toggles turtle mode by hand mid-window, we want the next tick to
+    /// leave it alone rather than re-assert the schedule's opinion.
+    last_alt_speed: Option<bool>,
+    cache: Option<TorrentCacheHandle>,
+    /// Window labels subscribed to this poller's `torrent-update` broadcast.
+    /// Several windows can point at the same server, so the torrent list is
+    /// serialized once per poll and the same buffer is dispatched to each.
+    subscribers: Arc<StdMutex<Vec<String>>>,
+}
+
+impl Default for Poller {
+    fn default() -> Self {
+        Poller {
+            app: None,
+            config: None,
+            client: reqwest::Client::new(),
+            session_id: String::new(),
+            stats_tx: None,
+            schedule: None,
+            last_alt_speed: None,
+            cache: None,
+            subscribers: Arc::new(StdMutex::new(Vec::new())),
+        }
+    }
+}
+
+impl Poller {
+    pub fn set_app_handle(&mut self, app: &Arc<AppHandle>, window_label: &str) {
+        self.app = Some(app.clone());
+        self.schedule = speed_schedule::load(app);
+        self.add_subscriber(window_label);
+
+        let cache_state: State<TorrentCacheHandle> = app.state();
+        self.cache = Some(cache_state.inner().clone());
+
+        // There is only one tray, so only the main window's poller drives
+        // it; extra server windows (and any other window sharing this same
+        // poller, see `broadcast_stats`) get their stats via a per-window
+        // event instead, or every poller would fight over the same
+        // menu/tooltip.
+        if window_label == "main" {
+            let (tx, rx) = mpsc::channel(8);
+            self.stats_tx = Some(tx);
+            tray::spawn_stats_listener(app.clone(), rx);
+        }
+    }
+
+    /// Point this poller at a specific torrent cache, overriding the
+    /// globally-managed one `set_app_handle` grabs by default. Used for
+    /// extra server windows, which get one cache per server they watch.
+    pub fn set_cache(&mut self, cache: TorrentCacheHandle) {
+        self.cache = Some(cache);
+    }
+
+    pub fn add_subscriber(&self, window_label: &str) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        if !subscribers.iter().any(|l| l == window_label) {
+            subscribers.push(window_label.to_string());
+        }
+    }
+
+    pub fn remove_subscriber(&self, window_label: &str) {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|l| l != window_label);
+    }
+
+    pub fn set_config(&mut self, config: PollerConfig) {
+        self.config = Some(config);
+    }
+
+    pub fn set_schedule(&mut self, schedule: SpeedSchedule) {
+        if let Some(app) = &self.app {
+            speed_schedule::save(app, &schedule);
+        }
+        self.schedule = Some(schedule);
+    }
+
+    async fn rpc_call(
+        &mut self,
+        method: &str,
+        arguments: serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let config = self.config.clone().ok_or("no server configured")?;
+        let body = json!({ "method": method, "arguments": arguments });
+
+        for attempt in 0..2 {
+            let mut req = self
+                .client
+                .post(&config.url)
+                .header("X-Transmission-Session-Id", self.session_id.clone())
+                .json(&body);
+            if let Some(user) = &config.username {
+                req = req.basic_auth(user, config.password.clone());
+            }
+
+            let resp = req.send().await.map_err(|e| e.to_string())?;
+            if resp.status() == reqwest::StatusCode::CONFLICT && attempt == 0 {
+                if let Some(id) = resp.headers().get("X-Transmission-Session-Id") {
+                    self.session_id = id.to_str().unwrap_or_default().to_string();
+                    continue;
+                }
+            }
+            return resp
+                .json::<serde_json::Value>()
+                .await
+                .map_err(|e| e.to_string());
+        }
+        Err("unable to negotiate session id".into())
+    }
+
+    async fn fetch_stats(&mut self) -> Result<AggregateStats, String> {
+        let fields = json!([
+            "id",
+            "name",
+            "status",
+            "rateDownload",
+            "rateUpload",
+            "activityDate",
+            "percentDone",
+            "error",
+            "errorString",
+        ]);
+        let reply = self
+            .rpc_call("torrent-get", json!({ "fields": fields }))
+            .await?;
+
+        let torrents = reply["arguments"]["torrents"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+
+        let mut stats = AggregateStats::default();
+        let mut recent: Vec<(i64, RecentTorrent)> = vec![];
+
+        for t in &torrents {
+            let status = t["status"].as_i64().unwrap_or(0);
+            stats.download_rate += t["rateDownload"].as_i64().unwrap_or(0);
+            stats.upload_rate += t["rateUpload"].as_i64().unwrap_or(0);
+            match status {
+                0 => stats.paused += 1,
+                6 => stats.seeding += 1,
+                _ => stats.active += 1,
+            }
+            recent.push((
+                t["activityDate"].as_i64().unwrap_or(0),
+                RecentTorrent {
+                    id: t["id"].as_i64().unwrap_or(0),
+                    name: t["name"].as_str().unwrap_or_default().to_string(),
+                    status,
+                },
+            ));
+        }
+
+        recent.sort_by(|a, b| b.0.cmp(&a.0));
+        stats.recent = recent
+            .into_iter()
+            .take(RECENT_TORRENTS_LIMIT)
+            .map(|(_, t)| t)
+            .collect();
+
+        stats.turtle_mode = self.fetch_alt_speed_enabled().await.unwrap_or(false);
+
+        self.notify_changes(&torrents);
+        self.broadcast_torrents(&torrents);
+
+        Ok(stats)
+    }
+
+    /// Serialize the torrent list once and dispatch the same buffer to
+    /// every window subscribed to this poller, instead of re-serializing
+    /// per window — this is what matters when several windows are watching
+    /// a server with a large torrent list.
+    fn broadcast_torrents(&self, torrents: &[serde_json::Value]) {
+        let Some(app) = &self.app else { return };
+        let subscribers = self.subscribers.lock().unwrap().clone();
+        if subscribers.is_empty() {
+            return;
+        }
+
+        let Ok(payload) = serde_json::to_string(&json!({ "torrents": torrents })) else {
+            return;
+        };
+        let Ok(raw) = RawValue::from_string(payload) else {
+            return;
+        };
+
+        for label in &subscribers {
+            if let Some(window) = app.get_window(label) {
+                window.emit("torrent-update", &raw).ok();
+            }
+        }
+    }
+
+    /// Push the aggregate stats to every subscribed window other than
+    /// "main" (which gets them via `stats_tx`/the tray instead). Several
+    /// windows can share this poller when they're watching the same server,
+    /// so this reaches all of them, not just whichever one happened to be
+    /// first.
+    fn broadcast_stats(&self, stats: &AggregateStats) {
+        let Some(app) = &self.app else { return };
+        let subscribers = self.subscribers.lock().unwrap().clone();
+        for label in &subscribers {
+            if label == "main" {
+                continue;
+            }
+            if let Some(window) = app.get_window(label) {
+                window.emit("server-stats", stats).ok();
+            }
+        }
+    }
+
+    /// Diff the freshly-polled torrents against the cached previous state
+    /// and fire notifications for completions and new errors. The very
+    /// first poll after startup only seeds the cache: otherwise every
+    /// already-complete torrent would notify on launch.
+    fn notify_changes(&self, torrents: &[serde_json::Value]) {
+        let Some(cache) = &self.cache else { return };
+        let notifications_enabled = self
+            .config
+            .as_ref()
+            .map(|c| c.notifications_enabled)
+            .unwrap_or(false);
+
+        let mut cache = cache.0.lock().unwrap();
+        let seed_only = !cache.is_primed();
+        let mut live_ids = Vec::with_capacity(torrents.len());
+
+        for t in torrents {
+            let id = t["id"].as_i64().unwrap_or(0);
+            let name = t["name"].as_str().unwrap_or_default();
+            let percent_done = t["percentDone"].as_f64().unwrap_or(0.0);
+            let status = t["status"].as_i64().unwrap_or(0);
+            let error = t["error"].as_i64().unwrap_or(0);
+            live_ids.push(id);
+
+            let previous = cache.get(id);
+            cache.set(
+                id,
+                CachedTorrent {
+                    percent_done,
+                    status,
+                    error,
+                },
+            );
+
+            if seed_only || !notifications_enabled {
+                continue;
+            }
+            if let Some(previous) = previous {
+                if previous.percent_done < 1.0 && (percent_done == 1.0 || status == 6) {
+                    self.notify("TrguiNG", &format!("Download complete: {}", name));
+                }
+                if previous.error == 0 && error != 0 {
+                    let error_string = t["errorString"].as_str().unwrap_or("Unknown error");
+                    self.notify(&format!("TrguiNG: {}", name), error_string);
+                }
+            }
+        }
+
+        cache.retain(&live_ids);
+        cache.mark_primed();
+    }
+
+    fn notify(&self, title: &str, body: &str) {
+        let Some(app) = &self.app else { return };
+        Notification::new(&app.config().tauri.bundle.identifier)
+            .title(title)
+            .body(body)
+            .show()
+            .ok();
+    }
+
+    pub async fn current_turtle_mode(&mut self) -> Result<bool, String> {
+        self.fetch_alt_speed_enabled().await
+    }
+
+    async fn fetch_alt_speed_enabled(&mut self) -> Result<bool, String> {
+        let reply = self
+            .rpc_call("session-get", json!({ "fields": ["alt-speed-enabled"] }))
+            .await?;
+        Ok(reply["arguments"]["alt-speed-enabled"]
+            .as_bool()
+            .unwrap_or(false))
+    }
+
+    pub async fn set_alt_speed_enabled(&mut self, enabled: bool) -> Result<(), String> {
+        self.rpc_call("session-set", json!({ "alt-speed-enabled": enabled }))
+            .await
+            .map(|_| ())
+    }
+
+    /// Apply the speed schedule for "now", flipping `alt-speed-enabled` only
+    /// when the window just opened or closed, and never when the user asked
+    /// us to defer to the daemon's own schedule.
+    async fn apply_schedule(&mut self) {
+        let Some(schedule) = self.schedule else {
+            return;
+        };
+        if !schedule.enabled || schedule.defer_to_server {
+            return;
+        }
+
+        let now = chrono::Local::now();
+        let minute_of_day = (now.hour() * 60 + now.minute()) as u16;
+        // chrono's Sunday = 0 matches the bit layout documented on SpeedSchedule.
+        let day_bit = 1u8 << now.weekday().num_days_from_sunday();
+        let previous_day_bit = 1u8 << now.weekday().pred().num_days_from_sunday();
+        let should_be_active = schedule.is_active_at(minute_of_day, day_bit, previous_day_bit);
+
+        if self.last_alt_speed == Some(should_be_active) {
+            return;
+        }
+        match self.set_alt_speed_enabled(should_be_active).await {
+            Ok(()) => self.last_alt_speed = Some(should_be_active),
+            Err(e) => println!("poller: failed to apply speed schedule: {}", e),
+        }
+    }
+
+    pub async fn poll_once(&mut self) {
+        if self.config.is_none() {
+            return;
+        }
+        self.apply_schedule().await;
+        match self.fetch_stats().await {
+            Ok(stats) => {
+                self.broadcast_stats(&stats);
+                if let Some(tx) = &self.stats_tx {
+                    tx.send(stats).await.ok();
+                }
+            }
+            Err(e) => println!("poller: failed to fetch stats: {}", e),
+        }
+    }
+
+    /// Start (`active`) or stop every known torrent, or just the given ids.
+    pub async fn set_torrents_active(
+        &mut self,
+        ids: Option<Vec<i64>>,
+        active: bool,
+    ) -> Result<(), String> {
+        let method = if active {
+            "torrent-start"
+        } else {
+            "torrent-stop"
+        };
+        let arguments = match ids {
+            Some(ids) => json!({ "ids": ids }),
+            None => json!({}),
+        };
+        self.rpc_call(method, arguments).await.map(|_| ())
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(
+            self.config
+                .as_ref()
+                .map(|c| c.interval_secs)
+                .unwrap_or(DEFAULT_INTERVAL_SECS),
+        )
+    }
+}
+
+#[derive(Clone)]
+pub struct PollerHandle(pub Arc<Mutex<Poller>>, Arc<AtomicBool>);
+
+impl Default for PollerHandle {
+    fn default() -> Self {
+        let poller = Arc::new(Mutex::new(Poller::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+        Self::spawn_poll_loop(poller.clone(), stop.clone());
+        PollerHandle(poller, stop)
+    }
+}
+
+impl PollerHandle {
+    /// Build a new, independent poller for a server window. Each distinct
+    /// server opened through `commands::open_server_window` gets its own so
+    /// it can poll a different daemon than the main window; windows sharing
+    /// a server share this same handle instead of creating another one.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop this poller's background loop after its current tick. Called
+    /// once the last window watching it closes, so the task doesn't outlive
+    /// every window that cared about its results.
+    pub fn stop(&self) {
+        self.1.store(true, Ordering::Relaxed);
+    }
+
+    fn spawn_poll_loop(poller: Arc<Mutex<Poller>>, stop: Arc<AtomicBool>) {
+        tauri::async_runtime::spawn(async move {
+            while !stop.load(Ordering::Relaxed) {
+                let interval = {
+                    let mut poller = poller.lock().await;
+                    poller.poll_once().await;
+                    poller.interval()
+                };
+                tokio::time::sleep(interval).await;
+            }
+        });
+    }
+}
@@ -0,0 +1,78 @@
+// transgui-ng - next gen remote GUI for transmission torrent daemon
+// Copyright (C) 2022  qu1ck (mail at qu1ck.org)
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+const STATE_FILE: &str = "speed-schedule.json";
+
+/// Scheduled alternative-speed-limit window, mirroring Transmission's own
+/// `alt-speed-time-*` session settings but driven from our side so the tray
+/// can reflect and toggle it without a round trip through the frontend.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+pub struct SpeedSchedule {
+    pub enabled: bool,
+    /// Minutes since midnight, local time, in [0, 1440).
+    pub begin_minute: u16,
+    pub end_minute: u16,
+    /// Bit 0 = Sunday .. bit 6 = Saturday, same layout transmission uses.
+    pub days: u8,
+    /// When true, we never touch `alt-speed-enabled` ourselves: the daemon
+    /// already has its own schedule configured and we'd just fight it.
+    pub defer_to_server: bool,
+}
+
+impl SpeedSchedule {
+    /// Whether the schedule's window is open for the given local
+    /// minute-of-day, using `day_bit`/`previous_day_bit` (same bit layout as
+    /// `days`) for today and yesterday respectively. When the window wraps
+    /// past midnight (`begin_minute > end_minute`), the pre-midnight part is
+    /// scheduled on today's day but the post-midnight tail is scheduled on
+    /// *yesterday's* — a "weekdays 23:00-06:00" schedule must still be open
+    /// at 01:00 Saturday, even though Saturday itself isn't a scheduled day.
+    pub fn is_active_at(&self, minute_of_day: u16, day_bit: u8, previous_day_bit: u8) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if self.begin_minute <= self.end_minute {
+            self.days & day_bit != 0
+                && minute_of_day >= self.begin_minute
+                && minute_of_day < self.end_minute
+        } else if minute_of_day >= self.begin_minute {
+            self.days & day_bit != 0
+        } else {
+            minute_of_day < self.end_minute && self.days & previous_day_bit != 0
+        }
+    }
+}
+
+pub fn load(app: &AppHandle) -> Option<SpeedSchedule> {
+    let path = app.path_resolver().app_config_dir()?.join(STATE_FILE);
+    let data = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+pub fn save(app: &AppHandle, schedule: &SpeedSchedule) {
+    let Some(dir) = app.path_resolver().app_config_dir() else {
+        return;
+    };
+    if std::fs::create_dir_all(&dir).is_err() {
+        return;
+    }
+    if let Ok(data) = serde_json::to_string_pretty(schedule) {
+        std::fs::write(dir.join(STATE_FILE), data).ok();
+    }
+}
@@ -0,0 +1,66 @@
+// transgui-ng - next gen remote GUI for transmission torrent daemon
+// Copyright (C) 2022  qu1ck (mail at qu1ck.org)
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Torrent fields the backend needs to remember between polls, kept
+/// deliberately small since the full torrent list lives in the frontend.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CachedTorrent {
+    pub percent_done: f64,
+    pub status: i64,
+    pub error: i64,
+}
+
+#[derive(Default)]
+pub struct TorrentCache {
+    by_id: HashMap<i64, CachedTorrent>,
+    /// Set once the first poll after startup has seeded the cache, so
+    /// callers can tell "nothing changed" apart from "nothing to compare to".
+    primed: bool,
+}
+
+impl TorrentCache {
+    pub fn get(&self, id: i64) -> Option<CachedTorrent> {
+        self.by_id.get(&id).copied()
+    }
+
+    pub fn set(&mut self, id: i64, torrent: CachedTorrent) {
+        self.by_id.insert(id, torrent);
+    }
+
+    /// Drop ids that are no longer present in the latest torrent list.
+    pub fn retain(&mut self, live_ids: &[i64]) {
+        let live: std::collections::HashSet<i64> = live_ids.iter().copied().collect();
+        self.by_id.retain(|id, _| live.contains(id));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_id.is_empty()
+    }
+
+    pub fn is_primed(&self) -> bool {
+        self.primed
+    }
+
+    pub fn mark_primed(&mut self) {
+        self.primed = true;
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct TorrentCacheHandle(pub Arc<Mutex<TorrentCache>>);
@@ -18,21 +18,187 @@ use std::sync::{Arc, Mutex};
 
 use tauri::{
     async_runtime, AppHandle, CustomMenuItem, Manager, State, SystemTray, SystemTrayEvent,
-    SystemTrayMenu, SystemTrayMenuItem, Window, WindowBuilder,
+    SystemTrayMenu, SystemTrayMenuItem, SystemTraySubmenu, Window, WindowBuilder,
 };
-use tokio::sync::oneshot;
+use tokio::sync::{mpsc, oneshot};
 
+use crate::autostart;
+use crate::poller::{AggregateStats, PollerHandle};
+use crate::window_state;
+use crate::windows::WindowManager;
 use crate::ListenerHandle;
 
+/// Title of the showhide item, shared between the window toggle and the
+/// periodic menu rebuild so rebuilding the menu never reverts it.
+static SHOWHIDE_TITLE: Mutex<Option<String>> = Mutex::new(None);
+
+const PAUSE_ALL: &str = "pause-all";
+const RESUME_ALL: &str = "resume-all";
+const TOGGLE_TURTLE: &str = "toggle-turtle";
+const TOGGLE_AUTOSTART: &str = "toggle-autostart";
+const RECENT_PREFIX_PAUSE: &str = "pause-torrent-";
+const RECENT_PREFIX_RESUME: &str = "resume-torrent-";
+const TOGGLE_WINDOW_PREFIX: &str = "toggle-window-";
+
 pub fn create_tray() -> SystemTray {
     let hide = CustomMenuItem::new("showhide".to_string(), "Hide");
     let quit = CustomMenuItem::new("quit".to_string(), "Quit");
-    let tray_menu = SystemTrayMenu::new()
+    let tray_menu = disconnected_menu(hide, quit);
+
+    SystemTray::new()
+        .with_menu(tray_menu)
+        .with_tooltip("Disconnected")
+}
+
+fn autostart_item() -> CustomMenuItem {
+    let item = CustomMenuItem::new(TOGGLE_AUTOSTART, "Start on login");
+    if autostart::is_enabled() {
+        item.selected()
+    } else {
+        item
+    }
+}
+
+fn disconnected_menu(hide: CustomMenuItem, quit: CustomMenuItem) -> SystemTrayMenu {
+    SystemTrayMenu::new()
+        .add_item(hide)
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(CustomMenuItem::new(PAUSE_ALL, "Pause all").disabled())
+        .add_item(CustomMenuItem::new(RESUME_ALL, "Resume all").disabled())
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(autostart_item())
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(quit)
+}
+
+/// Build the "Windows" submenu listing every server window beyond the main
+/// one, with a show/hide toggle for each.
+fn windows_submenu(app: &AppHandle, open_windows: &[(String, String)]) -> SystemTrayMenu {
+    let mut menu = SystemTrayMenu::new();
+    for (label, server_id) in open_windows {
+        let visible = app
+            .get_window(label)
+            .map(|w| w.is_visible().unwrap_or(true))
+            .unwrap_or(false);
+        let icon = if visible { "●" } else { "○" };
+        menu = menu.add_item(CustomMenuItem::new(
+            format!("{}{}", TOGGLE_WINDOW_PREFIX, label),
+            format!("{} {}", icon, server_id),
+        ));
+    }
+    if open_windows.is_empty() {
+        menu = menu.add_item(CustomMenuItem::new("no-windows", "No extra windows").disabled());
+    }
+    menu
+}
+
+/// Rebuild the tray menu from a fresh stats snapshot: showhide/quit stay put,
+/// pause/resume-all become enabled, and a submenu lists the most recently
+/// active torrents with per-item pause/resume.
+fn build_menu(
+    app: &AppHandle,
+    showhide_title: &str,
+    stats: &AggregateStats,
+    open_windows: &[(String, String)],
+) -> SystemTrayMenu {
+    let hide = CustomMenuItem::new("showhide".to_string(), showhide_title);
+    let quit = CustomMenuItem::new("quit".to_string(), "Quit");
+
+    let mut recent_menu = SystemTrayMenu::new();
+    for torrent in &stats.recent {
+        let is_paused = torrent.status == 0;
+        let action = if is_paused {
+            format!("{}{}", RECENT_PREFIX_RESUME, torrent.id)
+        } else {
+            format!("{}{}", RECENT_PREFIX_PAUSE, torrent.id)
+        };
+        let label = if is_paused {
+            format!("▶ {}", torrent.name)
+        } else {
+            format!("⏸ {}", torrent.name)
+        };
+        recent_menu = recent_menu.add_item(CustomMenuItem::new(action, label));
+    }
+    if stats.recent.is_empty() {
+        recent_menu =
+            recent_menu.add_item(CustomMenuItem::new("no-torrents", "No torrents").disabled());
+    }
+
+    let mut turtle = CustomMenuItem::new(TOGGLE_TURTLE, "Turtle mode");
+    if stats.turtle_mode {
+        turtle = turtle.selected();
+    }
+
+    SystemTrayMenu::new()
         .add_item(hide)
         .add_native_item(SystemTrayMenuItem::Separator)
-        .add_item(quit);
+        .add_item(CustomMenuItem::new(PAUSE_ALL, "Pause all"))
+        .add_item(CustomMenuItem::new(RESUME_ALL, "Resume all"))
+        .add_item(turtle)
+        .add_submenu(SystemTraySubmenu::new("Recent torrents", recent_menu))
+        .add_submenu(SystemTraySubmenu::new(
+            "Windows",
+            windows_submenu(app, open_windows),
+        ))
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(autostart_item())
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(quit)
+}
+
+fn format_title(stats: &AggregateStats) -> String {
+    let turtle = if stats.turtle_mode { "🐢 " } else { "" };
+    format!(
+        "{}↓ {}/s  ↑ {}/s",
+        turtle,
+        human_rate(stats.download_rate),
+        human_rate(stats.upload_rate)
+    )
+}
 
-    SystemTray::new().with_menu(tray_menu)
+fn human_rate(bytes_per_sec: i64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut rate = bytes_per_sec as f64;
+    let mut unit = 0;
+    while rate >= 1024.0 && unit < UNITS.len() - 1 {
+        rate /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", rate, UNITS[unit])
+}
+
+/// Listen for aggregate stats pushed by the poller, throttled to one tray
+/// update per poll tick, and keep the showhide label in sync with the menu
+/// we rebuild around it.
+pub fn spawn_stats_listener(app: Arc<AppHandle>, mut rx: mpsc::Receiver<AggregateStats>) {
+    async_runtime::spawn(async move {
+        while let Some(stats) = rx.recv().await {
+            let showhide_title = current_showhide_title(&app);
+            let open_windows: State<WindowManager> = app.state();
+            let menu = build_menu(&app, &showhide_title, &stats, &open_windows.labels());
+            let title = format_title(&stats);
+            app.tray_handle().set_menu(menu).ok();
+            app.tray_handle().set_tooltip(&title).ok();
+            // Only macOS renders a tray title next to the icon; elsewhere
+            // this is a harmless no-op and the tooltip is all that's shown.
+            app.tray_handle().set_title(&title).ok();
+        }
+    });
+}
+
+fn current_showhide_title(app: &AppHandle) -> String {
+    SHOWHIDE_TITLE.lock().unwrap().clone().unwrap_or_else(|| {
+        if app.get_window("main").is_some() {
+            "Hide"
+        } else {
+            "Show"
+        }
+        .to_string()
+    })
+}
+
+pub(crate) fn set_showhide_title(title: &str) {
+    *SHOWHIDE_TITLE.lock().unwrap() = Some(title.to_string());
 }
 
 pub fn on_tray_event(app: &AppHandle, event: SystemTrayEvent) {
@@ -48,18 +214,102 @@ pub fn on_tray_event(app: &AppHandle, event: SystemTrayEvent) {
             "showhide" => {
                 toggle_main_window(app.clone(), main_window);
             }
+            PAUSE_ALL => set_all_active(app.clone(), false),
+            RESUME_ALL => set_all_active(app.clone(), true),
+            TOGGLE_TURTLE => toggle_turtle_mode(app.clone()),
+            TOGGLE_AUTOSTART => toggle_autostart(app.clone()),
+            id if id.starts_with(RECENT_PREFIX_PAUSE) => set_torrent_active(
+                app.clone(),
+                parse_torrent_id(id, RECENT_PREFIX_PAUSE),
+                false,
+            ),
+            id if id.starts_with(RECENT_PREFIX_RESUME) => set_torrent_active(
+                app.clone(),
+                parse_torrent_id(id, RECENT_PREFIX_RESUME),
+                true,
+            ),
+            id if id.starts_with(TOGGLE_WINDOW_PREFIX) => {
+                toggle_server_window(app.clone(), &id[TOGGLE_WINDOW_PREFIX.len()..])
+            }
             _ => {}
         },
         _ => {}
     }
 }
 
+fn parse_torrent_id(id: &str, prefix: &str) -> i64 {
+    id[prefix.len()..].parse().unwrap_or(0)
+}
+
+fn set_all_active(app: AppHandle, active: bool) {
+    async_runtime::spawn(async move {
+        let poller_state: State<PollerHandle> = app.state();
+        let mut poller = poller_state.0.lock().await;
+        if let Err(e) = poller.set_torrents_active(None, active).await {
+            println!(
+                "tray: failed to {} all torrents: {}",
+                if active { "resume" } else { "pause" },
+                e
+            );
+        }
+    });
+}
+
+fn toggle_turtle_mode(app: AppHandle) {
+    async_runtime::spawn(async move {
+        let poller_state: State<PollerHandle> = app.state();
+        let mut poller = poller_state.0.lock().await;
+        match poller.current_turtle_mode().await {
+            Ok(enabled) => {
+                if let Err(e) = poller.set_alt_speed_enabled(!enabled).await {
+                    println!("tray: failed to toggle turtle mode: {}", e);
+                }
+            }
+            Err(e) => println!("tray: failed to read turtle mode: {}", e),
+        }
+    });
+}
+
+fn toggle_server_window(app: AppHandle, label: &str) {
+    let Some(window) = app.get_window(label) else {
+        return;
+    };
+    let visible = window.is_visible().unwrap_or(true);
+    if visible {
+        window.hide().ok();
+    } else {
+        window.show().ok();
+        window.set_focus().ok();
+    }
+}
+
+fn toggle_autostart(app: AppHandle) {
+    let enabled = !autostart::is_enabled();
+    if let Err(e) = autostart::set_enabled(enabled) {
+        println!("tray: failed to update autostart registration: {}", e);
+        return;
+    }
+    let item = app.tray_handle().get_item(TOGGLE_AUTOSTART);
+    item.set_selected(enabled).ok();
+}
+
+fn set_torrent_active(app: AppHandle, id: i64, active: bool) {
+    async_runtime::spawn(async move {
+        let poller_state: State<PollerHandle> = app.state();
+        let mut poller = poller_state.0.lock().await;
+        if let Err(e) = poller.set_torrents_active(Some(vec![id]), active).await {
+            println!("tray: failed to toggle torrent {}: {}", id, e);
+        }
+    });
+}
+
 fn toggle_main_window(app: AppHandle, window: Option<Window>) {
     match window {
         Some(window) => {
             async_runtime::spawn(async move {
                 close_main(window).await;
 
+                set_showhide_title("Show");
                 app.tray_handle()
                     .get_item("showhide".into())
                     .set_title("Show\0")
@@ -67,15 +317,24 @@ fn toggle_main_window(app: AppHandle, window: Option<Window>) {
             });
         }
         None => {
+            // Stay hidden until the saved geometry is applied, or the window
+            // flashes at its default position/size before jumping to the
+            // restored one.
             let window =
                 WindowBuilder::new(&app, "main", tauri::WindowUrl::App("index.html".into()))
+                    .visible(false)
                     .build()
                     .unwrap();
+            if let Some(state) = window_state::load(&app) {
+                window_state::apply(&window, &state);
+            }
+            set_showhide_title("Hide");
             app.tray_handle()
                 .get_item("showhide".into())
                 .set_title("Hide\0")
                 .ok();
             window.set_title("Transmission Remote GUI").ok();
+            window.show().ok();
             window.set_focus().ok();
         }
     }
@@ -104,5 +363,6 @@ async fn close_main(window: Window) {
     });
     window.emit("exit-requested", ()).ok();
     rx.await.ok();
+    window_state::save(&window.app_handle(), &window);
     window.close().ok();
 }
@@ -0,0 +1,117 @@
+// transgui-ng - next gen remote GUI for transmission torrent daemon
+// Copyright (C) 2022  qu1ck (mail at qu1ck.org)
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, LogicalPosition, LogicalSize, Window};
+
+const STATE_FILE: &str = "window-state.json";
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct WindowState {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub maximized: bool,
+    pub fullscreen: bool,
+}
+
+/// Capture the window's current geometry. Called right before we hide or
+/// destroy it, and on app exit, so the file on disk always reflects the
+/// last place the user actually left the window.
+pub fn capture(window: &Window) -> Option<WindowState> {
+    let scale = window.scale_factor().ok()?;
+    let position = window.outer_position().ok()?.to_logical::<i32>(scale);
+    let size = window.inner_size().ok()?.to_logical::<u32>(scale);
+
+    Some(WindowState {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        maximized: window.is_maximized().unwrap_or(false),
+        fullscreen: window.is_fullscreen().unwrap_or(false),
+    })
+}
+
+pub fn save(app: &AppHandle, window: &Window) {
+    let Some(state) = capture(window) else { return };
+    let Some(dir) = app.path_resolver().app_config_dir() else {
+        return;
+    };
+    if std::fs::create_dir_all(&dir).is_err() {
+        return;
+    }
+    if let Ok(data) = serde_json::to_string_pretty(&state) {
+        std::fs::write(dir.join(STATE_FILE), data).ok();
+    }
+}
+
+pub fn load(app: &AppHandle) -> Option<WindowState> {
+    let path = app.path_resolver().app_config_dir()?.join(STATE_FILE);
+    let data = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+/// Apply saved geometry to a freshly built window, clamping the restored
+/// position to whichever monitor is currently visible so a window last seen
+/// on a disconnected display can't open off-screen.
+pub fn apply(window: &Window, state: &WindowState) {
+    let (x, y) = clamp_to_visible_monitor(window, state.x, state.y, state.width, state.height);
+
+    window.set_position(LogicalPosition::new(x, y)).ok();
+    window
+        .set_size(LogicalSize::new(state.width, state.height))
+        .ok();
+    if state.maximized {
+        window.maximize().ok();
+    }
+    window.set_fullscreen(state.fullscreen).ok();
+}
+
+fn clamp_to_visible_monitor(
+    window: &Window,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+) -> (i32, i32) {
+    let Ok(monitors) = window.available_monitors() else {
+        return (x, y);
+    };
+
+    let fits_some_monitor = monitors.iter().any(|m| {
+        let pos = m.position();
+        let size = m.size();
+        x >= pos.x && y >= pos.y && x < pos.x + size.width as i32 && y < pos.y + size.height as i32
+    });
+    if fits_some_monitor {
+        return (x, y);
+    }
+
+    // Fall back to centering on the primary monitor, or to the origin
+    // (0, 0) if we can't even find one.
+    match window.primary_monitor().ok().flatten() {
+        Some(monitor) => {
+            let pos = monitor.position();
+            let size = monitor.size();
+            let x = pos.x + (size.width as i32 - width as i32).max(0) / 2;
+            let y = pos.y + (size.height as i32 - height as i32).max(0) / 2;
+            (x, y)
+        }
+        None => (0, 0),
+    }
+}
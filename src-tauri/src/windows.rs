@@ -0,0 +1,102 @@
+// transgui-ng - next gen remote GUI for transmission torrent daemon
+// Copyright (C) 2022  qu1ck (mail at qu1ck.org)
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::poller::PollerHandle;
+use crate::torrentcache::TorrentCacheHandle;
+
+/// One extra server window on top of the main one: the poller and torrent
+/// cache for its server (shared with any other window on that server) so it
+/// polls independently of whatever the main window is connected to.
+pub struct ServerWindow {
+    pub server_id: String,
+    pub poller: PollerHandle,
+    pub cache: TorrentCacheHandle,
+}
+
+/// Tracks every server window opened beyond the main one, keyed by window
+/// label, so the tray can list them and `commands::open_server_window` can
+/// hand each a fresh label. Windows pointed at the same `server_id` share a
+/// single poller and cache, so the torrent list for that server is only
+/// ever fetched and serialized once per poll no matter how many windows are
+/// watching it.
+#[derive(Default)]
+pub struct WindowManager {
+    windows: Mutex<HashMap<String, ServerWindow>>,
+    next_id: AtomicUsize,
+}
+
+impl WindowManager {
+    pub fn next_label(&self) -> String {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        format!("server-{}", id)
+    }
+
+    /// The poller and cache already watching `server_id`, if some other
+    /// open window is connected to it.
+    pub fn shared(&self, server_id: &str) -> Option<(PollerHandle, TorrentCacheHandle)> {
+        self.windows
+            .lock()
+            .unwrap()
+            .values()
+            .find(|w| w.server_id == server_id)
+            .map(|w| (w.poller.clone(), w.cache.clone()))
+    }
+
+    /// The poller behind a specific window label, for routing a config
+    /// update to the right server instead of always the main one.
+    pub fn poller(&self, label: &str) -> Option<PollerHandle> {
+        self.windows
+            .lock()
+            .unwrap()
+            .get(label)
+            .map(|w| w.poller.clone())
+    }
+
+    pub fn insert(&self, label: String, window: ServerWindow) {
+        self.windows.lock().unwrap().insert(label, window);
+    }
+
+    pub fn remove(&self, label: &str) -> Option<ServerWindow> {
+        self.windows.lock().unwrap().remove(label)
+    }
+
+    /// Whether any window this manager tracks is still watching `server_id`,
+    /// used after removing a window to decide whether its poller can be
+    /// stopped or is still shared.
+    pub fn has_window_for(&self, server_id: &str) -> bool {
+        self.windows
+            .lock()
+            .unwrap()
+            .values()
+            .any(|w| w.server_id == server_id)
+    }
+
+    /// `(label, server_id)` for every window this manager tracks, for the
+    /// tray's "Windows" submenu. The main window isn't tracked here since it
+    /// already has its own showhide item.
+    pub fn labels(&self) -> Vec<(String, String)> {
+        self.windows
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(label, window)| (label.clone(), window.server_id.clone()))
+            .collect()
+    }
+}